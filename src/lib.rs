@@ -0,0 +1,3 @@
+pub mod once_vec;
+
+pub use once_vec::{OnceVec, OnceVecError};
@@ -1,6 +1,5 @@
 use std::{
     cell::UnsafeCell,
-    collections::TryReserveError,
     iter::repeat_n,
     mem::{ManuallyDrop, MaybeUninit},
     sync::{
@@ -8,42 +7,155 @@ use std::{
         Once,
     },
 };
+
+use allocator_api2::{
+    alloc::{Allocator, Global},
+    boxed::Box,
+    collections::TryReserveError,
+    vec::Vec,
+};
+use rayon::iter::{
+    plumbing::{Consumer, Folder, Reducer, UnindexedConsumer},
+    IndexedParallelIterator,
+};
+
 #[derive(Debug)]
 pub struct OnceVecError;
 #[derive(Debug)]
-pub struct OnceVec<T> {
-    vec: Vec<UnsafeCell<MaybeUninit<T>>>,
-    once: Vec<Once>,
+pub struct OnceVec<T, A: Allocator + Clone = Global> {
+    vec: Vec<UnsafeCell<MaybeUninit<T>>, A>,
+    once: Vec<Once, A>,
     elements_written: AtomicUsize,
 }
 
+// SAFETY: every slot is guarded by its own `Once`, so concurrent `try_write`s
+// to distinct indices never race with each other, and `elements_written` is
+// an `AtomicUsize`. Sharing `&OnceVec<T, A>` across threads also hands out
+// shared `&T` (via `as_slice`/`get`) and shared `&A` (via `allocator()`), so
+// both `T` and the allocator handle `A` must be `Sync` as well as `Send`,
+// just like `std`'s `Vec<T>: Sync` requires `T: Sync`.
+unsafe impl<T: Send + Sync, A: Allocator + Clone + Send + Sync> Sync for OnceVec<T, A> {}
+
+impl<T, A: Allocator + Clone> Drop for OnceVec<T, A> {
+    fn drop(&mut self) {
+        for (slot, once) in self.vec.iter_mut().zip(self.once.iter()) {
+            if once.is_completed() {
+                // SAFETY: `once` completed, so this slot holds a `T` that
+                // has not been dropped yet.
+                unsafe { slot.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+impl<T> Default for OnceVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> OnceVec<T> {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+
+    pub fn with_uninit_len(len: usize) -> Self {
+        Self::with_uninit_len_in(len, Global)
+    }
+
+    pub fn try_with_capacity(capacity: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_capacity_in(capacity, Global)
+    }
+
+    pub fn try_with_uninit_len(len: usize) -> Result<Self, TryReserveError> {
+        Self::try_with_uninit_len_in(len, Global)
+    }
+
+    pub fn from_par_iter_indexed<I>(len: usize, iter: I) -> Result<Self, OnceVecError>
+    where
+        T: Send + Sync,
+        I: IndexedParallelIterator<Item = T>,
+    {
+        if iter.len() != len {
+            return Err(OnceVecError);
+        }
+        let once_vec = Self::with_uninit_len(len);
+        iter.drive(IndexedWriteConsumer {
+            once_vec: &once_vec,
+            base: 0,
+        });
+        if once_vec.is_fully_written() {
+            Ok(once_vec)
+        } else {
+            Err(OnceVecError)
+        }
+    }
+}
+
+impl<T, A: Allocator + Clone> OnceVec<T, A> {
+    pub fn new_in(alloc: A) -> Self {
         Self {
-            vec: Vec::new(),
-            once: Vec::new(),
+            vec: Vec::new_in(alloc.clone()),
+            once: Vec::new_in(alloc),
             elements_written: AtomicUsize::new(0),
         }
     }
 
-    pub fn with_capacity(capacity: usize) -> Self {
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
         Self {
-            vec: Vec::with_capacity(capacity),
-            once: Vec::with_capacity(capacity),
+            vec: Vec::with_capacity_in(capacity, alloc.clone()),
+            once: Vec::with_capacity_in(capacity, alloc),
             elements_written: AtomicUsize::new(0),
         }
     }
 
-    pub fn with_uninit_len(len: usize) -> Self {
+    pub fn with_uninit_len_in(len: usize, alloc: A) -> Self {
+        let mut vec = Vec::with_capacity_in(len, alloc.clone());
+        vec.extend(repeat_n((), len).map(|_| UnsafeCell::new(MaybeUninit::uninit())));
+        let mut once = Vec::with_capacity_in(len, alloc);
+        once.extend(repeat_n((), len).map(|_| Once::new()));
         Self {
-            vec: repeat_n((), len)
-                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
-                .collect(),
-            once: repeat_n((), len).map(|_| Once::new()).collect(),
+            vec,
+            once,
             elements_written: AtomicUsize::new(0),
         }
     }
 
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let mut vec = Vec::new_in(alloc.clone());
+        vec.try_reserve_exact(capacity)?;
+        let mut once = Vec::new_in(alloc);
+        once.try_reserve_exact(capacity)?;
+        Ok(Self {
+            vec,
+            once,
+            elements_written: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn try_with_uninit_len_in(len: usize, alloc: A) -> Result<Self, TryReserveError> {
+        let mut vec = Vec::new_in(alloc.clone());
+        vec.try_reserve_exact(len)?;
+        let mut once = Vec::new_in(alloc);
+        once.try_reserve_exact(len)?;
+        vec.extend(repeat_n((), len).map(|_| UnsafeCell::new(MaybeUninit::uninit())));
+        once.extend(repeat_n((), len).map(|_| Once::new()));
+        Ok(Self {
+            vec,
+            once,
+            elements_written: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the allocator backing this `OnceVec`'s storage.
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
+    }
+
     pub fn capacity(&self) -> usize {
         self.vec.capacity()
     }
@@ -58,12 +170,18 @@ impl<T> OnceVec<T> {
         self.once.reserve_exact(additional);
     }
 
+    // NOTE: `vec`'s capacity is only ever used as an allocation hint, so if
+    // `once`'s reservation fails after `vec`'s succeeded, `vec` is left with
+    // surplus capacity but the two stay length-synchronized and the struct
+    // remains in a consistent state.
     pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
-        todo!();
+        self.vec.try_reserve(additional)?;
+        self.once.try_reserve(additional)
     }
 
     pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
-        todo!();
+        self.vec.try_reserve_exact(additional)?;
+        self.once.try_reserve_exact(additional)
     }
 
     pub fn shrink_to_fit(&mut self) {
@@ -72,13 +190,10 @@ impl<T> OnceVec<T> {
     }
 
     pub fn shrink_to(&mut self, min_capacity: usize) {
-        self.vec.shrink_to_fit();
-        self.once.shrink_to_fit();
+        self.vec.shrink_to(min_capacity);
+        self.once.shrink_to(min_capacity);
     }
 
-    // Still to decide if this should be implemented
-    // pub fn into_boxed_slice(self) -> Box<[T], A>
-
     pub fn truncate(&mut self, len: usize) {
         if len >= self.vec.len() {
             return;
@@ -120,20 +235,76 @@ impl<T> OnceVec<T> {
         }
     }
 
-    pub fn as_vec(mut self) -> Result<Vec<T>, OnceVecError> {
+    pub fn is_written(&self, index: usize) -> bool {
+        self.once.get(index).is_some_and(Once::is_completed)
+    }
+
+    pub fn written_len(&self) -> usize {
+        self.elements_written.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if !self.is_written(index) {
+            return None;
+        }
+        // SAFETY: `is_written` confirmed this slot's `Once` completed, so it
+        // holds an initialized `T`. The returned reference borrows `self`.
+        Some(unsafe { (*self.vec.get(index)?.get()).assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if !self.is_written(index) {
+            return None;
+        }
+        // SAFETY: `is_written` confirmed this slot's `Once` completed, so it
+        // holds an initialized `T`. The returned reference mutably borrows
+        // `self`, so no other access to this slot can be alive.
+        Some(unsafe { (*self.vec.get(index)?.get()).assume_init_mut() })
+    }
+
+    pub fn as_vec(self) -> Result<Vec<T, A>, OnceVecError> {
         if !self.is_fully_written() {
-            Err(OnceVecError)
-        } else {
-            let mut v = ManuallyDrop::new(self.vec);
-            let ptr = v.as_mut_ptr() as *mut T;
-            let length = v.len();
-            let capacity = v.capacity();
-            // SAFETY: We own `vec` so `ptr` results from an allocation with the global
-            // allocator. `MaybeUninit` is guarantied to have the same layout as `T`.
-            // Length and capacity are directly resulting from a safe vector so length
-            // must be smaller than or equal to capacity.
-            // We also check that the vector is fully written
-            Ok(unsafe { Vec::from_raw_parts(ptr, length, capacity) })
+            return Err(OnceVecError);
+        }
+        // `self` has a `Drop` impl, so its fields can't be moved out of it
+        // directly; wrap it in `ManuallyDrop` and pull `vec` out with a raw
+        // read instead, then drop the now-unneeded `once` vec ourselves.
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY: `this` will never be used again after these two lines and
+        // its destructor never runs (it is a `ManuallyDrop`), so reading
+        // `vec` out and separately dropping `once` in place does not alias
+        // or double-drop either field.
+        let vec = unsafe { std::ptr::read(&this.vec) };
+        unsafe { std::ptr::drop_in_place(&mut this.once) };
+        // Stash the allocator handle before `vec` is taken apart below, so
+        // the reconstructed `Vec<T, A>` keeps using the same allocator.
+        let alloc = vec.allocator().clone();
+        let mut v = ManuallyDrop::new(vec);
+        let ptr = v.as_mut_ptr() as *mut T;
+        let length = v.len();
+        let capacity = v.capacity();
+        // SAFETY: We own `vec` so `ptr` results from an allocation with `alloc`.
+        // `MaybeUninit` is guarantied to have the same layout as `T`.
+        // Length and capacity are directly resulting from a safe vector so length
+        // must be smaller than or equal to capacity.
+        // We also check that the vector is fully written
+        Ok(unsafe { Vec::from_raw_parts_in(ptr, length, capacity, alloc) })
+    }
+
+    pub fn into_boxed_slice(self) -> Result<Box<[T], A>, OnceVecError> {
+        self.as_vec().map(Vec::into_boxed_slice)
+    }
+
+    /// Drains and yields the currently-written elements, resetting their
+    /// `Once` slots (and `elements_written`) so the vector is considered
+    /// unwritten at those indices afterwards. Slots that were never written
+    /// are left untouched and are skipped. A slot is only drained when the
+    /// iterator reaches it: if the iterator is dropped early, the written
+    /// slots it has not reached stay written and keep their elements.
+    pub fn drain_written(&mut self) -> DrainWritten<'_, T, A> {
+        DrainWritten {
+            once_vec: self,
+            index: 0,
         }
     }
 
@@ -221,7 +392,7 @@ impl<T> OnceVec<T> {
             *element = val;
             once_check = true;
         });
-        if once_check == true {
+        if once_check {
             self.elements_written.fetch_add(1, Ordering::Relaxed);
             Ok(())
         } else {
@@ -230,7 +401,7 @@ impl<T> OnceVec<T> {
     }
 }
 
-impl<T> OnceVec<T> {
+impl<T, A: Allocator + Clone> OnceVec<T, A> {
     fn elements_written_until(&self, until: usize) -> usize {
         self.once
             .iter()
@@ -239,15 +410,123 @@ impl<T> OnceVec<T> {
             .count()
     }
 
-    fn elements_written(&self, until: usize) -> usize {
-        self.elements_written_until(self.once.len())
-    }
-
     fn is_fully_written(&self) -> bool {
         self.elements_written.load(Ordering::Relaxed) == self.once.len()
     }
 }
 
+/// Iterator returned by [`OnceVec::drain_written`].
+pub struct DrainWritten<'a, T, A: Allocator + Clone> {
+    once_vec: &'a mut OnceVec<T, A>,
+    index: usize,
+}
+
+impl<'a, T, A: Allocator + Clone> Iterator for DrainWritten<'a, T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.index < self.once_vec.once.len() {
+            let index = self.index;
+            self.index += 1;
+            if self.once_vec.once[index].is_completed() {
+                self.once_vec.once[index] = Once::new();
+                self.once_vec
+                    .elements_written
+                    .fetch_sub(1, Ordering::Relaxed);
+                // SAFETY: the slot's `Once` had just completed, so it holds
+                // an initialized `T`. We already reset its `Once` above, so
+                // this slot is considered unwritten and won't be read or
+                // dropped again.
+                let value = unsafe {
+                    std::mem::replace(&mut *self.once_vec.vec[index].get(), MaybeUninit::uninit())
+                        .assume_init()
+                };
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+/// A rayon `Consumer` that scatter-writes each item it receives into the
+/// slot `base + i` of a shared `OnceVec`, where `i` is the position of the
+/// item within the range this consumer was handed by `split_at`.
+struct IndexedWriteConsumer<'a, T> {
+    once_vec: &'a OnceVec<T>,
+    base: usize,
+}
+
+impl<'a, T: Send + Sync> Consumer<T> for IndexedWriteConsumer<'a, T> {
+    type Folder = IndexedWriteFolder<'a, T>;
+    type Reducer = NoopReducer;
+    type Result = ();
+
+    fn split_at(self, index: usize) -> (Self, Self, Self::Reducer) {
+        (
+            IndexedWriteConsumer {
+                once_vec: self.once_vec,
+                base: self.base,
+            },
+            IndexedWriteConsumer {
+                once_vec: self.once_vec,
+                base: self.base + index,
+            },
+            NoopReducer,
+        )
+    }
+
+    fn into_folder(self) -> Self::Folder {
+        IndexedWriteFolder {
+            once_vec: self.once_vec,
+            index: self.base,
+        }
+    }
+
+    fn full(&self) -> bool {
+        false
+    }
+}
+
+impl<'a, T: Send + Sync> UnindexedConsumer<T> for IndexedWriteConsumer<'a, T> {
+    fn split_off_left(&self) -> Self {
+        IndexedWriteConsumer {
+            once_vec: self.once_vec,
+            base: self.base,
+        }
+    }
+
+    fn to_reducer(&self) -> Self::Reducer {
+        NoopReducer
+    }
+}
+
+struct IndexedWriteFolder<'a, T> {
+    once_vec: &'a OnceVec<T>,
+    index: usize,
+}
+
+impl<'a, T: Send + Sync> Folder<T> for IndexedWriteFolder<'a, T> {
+    type Result = ();
+
+    fn consume(mut self, item: T) -> Self {
+        let _ = self.once_vec.try_write(self.index, item);
+        self.index += 1;
+        self
+    }
+
+    fn complete(self) -> Self::Result {}
+
+    fn full(&self) -> bool {
+        false
+    }
+}
+
+struct NoopReducer;
+
+impl Reducer<()> for NoopReducer {
+    fn reduce(self, _left: (), _right: ()) {}
+}
+
 #[test]
 fn first_test() {
     let mut once_vec: OnceVec<f32> = OnceVec::with_uninit_len(6);
@@ -268,5 +547,107 @@ fn first_test() {
         &[0.30, 0.31, 0.32, 0.33, 0.34, 0.40]
     );
     let vec = once_vec.as_vec().unwrap();
-    assert_eq!(vec, vec![0.30, 0.31, 0.32, 0.33, 0.34, 0.40]);
+    assert_eq!(
+        vec.as_slice(),
+        &[0.30, 0.31, 0.32, 0.33, 0.34, 0.40]
+    );
+}
+
+#[test]
+fn from_par_iter_indexed_fills_every_slot() {
+    use rayon::iter::IntoParallelIterator;
+
+    let once_vec: OnceVec<i32> =
+        OnceVec::from_par_iter_indexed(8, (0..8).into_par_iter()).unwrap();
+    let result = once_vec.as_vec().unwrap();
+    assert!(result.iter().copied().eq(0..8));
+}
+
+#[test]
+fn from_par_iter_indexed_rejects_short_iterator() {
+    use rayon::iter::IntoParallelIterator;
+
+    let result: Result<OnceVec<i32>, OnceVecError> =
+        OnceVec::from_par_iter_indexed(8, (0..4).into_par_iter());
+    assert!(result.is_err());
+}
+
+#[test]
+fn from_par_iter_indexed_rejects_long_iterator() {
+    use rayon::iter::IntoParallelIterator;
+
+    let result: Result<OnceVec<i32>, OnceVecError> =
+        OnceVec::from_par_iter_indexed(4, (0..8).into_par_iter());
+    assert!(result.is_err());
+}
+
+#[test]
+fn try_with_uninit_len_fills_len_slots() {
+    let once_vec: OnceVec<f32> = OnceVec::try_with_uninit_len(4).unwrap();
+    assert_eq!(once_vec.capacity(), 4);
+    let _ = once_vec.try_write(0, 1.0);
+    let _ = once_vec.try_write(1, 2.0);
+    let _ = once_vec.try_write(2, 3.0);
+    let _ = once_vec.try_write(3, 4.0);
+    assert_eq!(once_vec.as_slice().unwrap(), &[1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn get_and_get_mut_only_see_written_slots() {
+    let mut once_vec: OnceVec<f32> = OnceVec::with_uninit_len(3);
+    assert!(!once_vec.is_written(0));
+    assert_eq!(once_vec.get(0), None);
+    assert_eq!(once_vec.written_len(), 0);
+
+    let _ = once_vec.try_write(0, 1.5);
+    assert!(once_vec.is_written(0));
+    assert!(!once_vec.is_written(1));
+    assert_eq!(once_vec.get(0), Some(&1.5));
+    assert_eq!(once_vec.written_len(), 1);
+
+    *once_vec.get_mut(0).unwrap() = 2.5;
+    assert_eq!(once_vec.get(0), Some(&2.5));
+}
+
+#[test]
+fn get_and_get_mut_and_is_written_reject_out_of_range_index() {
+    let mut once_vec: OnceVec<f32> = OnceVec::with_uninit_len(1);
+    assert!(!once_vec.is_written(5));
+    assert_eq!(once_vec.get(5), None);
+    assert_eq!(once_vec.get_mut(5), None);
+}
+
+#[test]
+fn drain_written_yields_only_initialized_slots_and_resets_them() {
+    let mut once_vec: OnceVec<i32> = OnceVec::with_uninit_len(4);
+    let _ = once_vec.try_write(0, 10);
+    let _ = once_vec.try_write(2, 30);
+
+    let drained = once_vec.drain_written().collect::<std::vec::Vec<_>>();
+    assert_eq!(drained, vec![10, 30]);
+    assert_eq!(once_vec.written_len(), 0);
+    assert!(!once_vec.is_written(0));
+    assert!(!once_vec.is_written(2));
+}
+
+#[test]
+fn into_boxed_slice_requires_full_initialization() {
+    let once_vec: OnceVec<i32> = OnceVec::with_uninit_len(2);
+    assert!(once_vec.into_boxed_slice().is_err());
+
+    let once_vec: OnceVec<i32> = OnceVec::with_uninit_len(2);
+    let _ = once_vec.try_write(0, 1);
+    let _ = once_vec.try_write(1, 2);
+    let boxed = once_vec.into_boxed_slice().unwrap();
+    assert_eq!(&*boxed, &[1, 2]);
+}
+
+#[test]
+fn with_uninit_len_in_uses_the_given_allocator() {
+    let once_vec: OnceVec<i32, Global> = OnceVec::with_uninit_len_in(3, Global);
+    let _ = once_vec.try_write(0, 1);
+    let _ = once_vec.try_write(1, 2);
+    let _ = once_vec.try_write(2, 3);
+    let vec = once_vec.as_vec().unwrap();
+    assert_eq!(vec.as_slice(), &[1, 2, 3]);
 }